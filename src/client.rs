@@ -1,13 +1,18 @@
-use error::MonetError;
-use protocol::MapiProtocol;
+use crate::connection;
+use crate::connection::{Request, Response};
+use crate::protocol::codec::DEFAULT_MAX_BLOCK_LEN;
+use crate::protocol::{handshake, MapiMessage, Row};
 
-use futures::{future, Future};
+use futures::{stream, Stream, StreamExt};
 use std::io;
-use tokio_core::net::TcpStream;
-use tokio_core::reactor::Handle;
-use tokio_proto::{TcpClient};
-use tokio_proto::pipeline::{ClientService};
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
 
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+#[derive(Clone)]
 pub struct Config {
     socket: Option<String>,
     hostname: String,
@@ -16,6 +21,8 @@ pub struct Config {
     password: String,
     database: String,
     language: String,
+    max_redirects: usize,
+    max_block_len: usize,
 }
 
 // Fix this later... should there be a builder pattern here?
@@ -28,57 +35,239 @@ impl Default for Config {
             username: "monetdb".to_owned(),
             password: "monetdb".to_owned(),
             database: "monetdb".to_owned(),
-            language: "english".to_owned(),
+            language: "sql".to_owned(),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            max_block_len: DEFAULT_MAX_BLOCK_LEN,
         }
     }
 }
 
 pub struct Client {
-    inner: ClientService<TcpStream, MapiProtocol>,
+    requests: mpsc::UnboundedSender<Request>,
 }
 
-
 impl Client {
 
-    pub fn connect(&mut self, config: Config, handle: &Handle) -> Box<Future<Item=Client, Error=io::Error>> {
-        // use url concatenate
-        let addr = format!("mapi://{}:{}@{}:{}/{})",
-            config.username,
-            config.password,
-            config.hostname,
-            config.port,
-            config.database
-        );
-
-        let res = TcpClient::new(MapiProtocol)
-            .connect(&addr.parse().unwrap(), handle)
-            .map(|client_service| {
-                Client { inner: client_service }
+    pub async fn connect(config: Config) -> io::Result<Client> {
+        let mut config = config;
+        let mut redirects = 0;
+
+        loop {
+            match connect_once(&config).await {
+                Ok(client) => return Ok(client),
+                Err(err) => match handshake::redirect_target(&err) {
+                    Some(target) if redirects < config.max_redirects => {
+                        config = follow_redirect(&config, target)?;
+                        redirects += 1;
+                    }
+                    _ => return Err(err),
+                },
             }
-        );
-        Box::new(res)
+        }
     }
-}
 
-impl Service for Client {
-    type Request = String;
-    type Response = String;
-    type Error = io::Error;
-    type Future = Box<Future<Item=String, Error=io::Error>>;
+    /// Run a query and wait for its reply. For a query that opens a result
+    /// set, only the result set's header message is returned; its rows are
+    /// discarded.
+    pub async fn call(&self, sql: &str) -> io::Result<MapiMessage> {
+        match self.send(sql).await? {
+            Response::Message(message) => Ok(message),
+            Response::Stream(message, _rows) => Ok(message),
+        }
+    }
 
-    fn call(&self, req: String) -> Self::Future {
-        self.inner.call(req)
+    /// Run a query and stream its rows as they arrive, instead of buffering
+    /// the whole result set in memory.
+    pub async fn query_stream(&self, sql: &str) -> io::Result<Pin<Box<dyn Stream<Item = io::Result<Row>> + Send>>> {
+        let rows: Pin<Box<dyn Stream<Item = io::Result<Row>> + Send>> = match self.send(sql).await? {
+            Response::Stream(_, rx) => Box::pin(rx.filter_map(|message| async move {
+                match message {
+                    Ok(MapiMessage::Tuple(values)) => Some(Ok(Row(values))),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                }
+            })),
+            Response::Message(_) => Box::pin(stream::empty()),
+        };
+        Ok(rows)
     }
+
+    async fn send(&self, sql: &str) -> io::Result<Response> {
+        let (reply, reply_rx) = oneshot::channel();
+        self.requests
+            .send(Request { sql: sql.to_owned(), reply })
+            .map_err(|_| connection_gone())?;
+        reply_rx.await.map_err(|_| connection_gone())?
+    }
+}
+
+async fn connect_once(config: &Config) -> io::Result<Client> {
+    match config.socket {
+        Some(ref path) => connect_unix(config, path).await,
+        None => connect_tcp(config).await,
+    }
+}
+
+async fn connect_tcp(config: &Config) -> io::Result<Client> {
+    let stream = TcpStream::connect((config.hostname.as_str(), config.port as u16)).await?;
+    finish_connect(stream, config).await
 }
 
-fn parse_error_str(s: &str) -> (MonetError, &str) {
-    use self::MonetError::*;
+#[cfg(unix)]
+async fn connect_unix(config: &Config, path: &str) -> io::Result<Client> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path).await?;
+    // A Unix socket transport opens with a single "0" byte before the usual
+    // salt/challenge block handshake - a fixed preamble the reference client
+    // libraries send on this path, not a permission check or passphrase.
+    stream.write_all(b"0").await?;
+    finish_connect(stream, config).await
+}
 
-    if s.len() > 6 {
-        if let Some(err) = MonetError::from_mapi_code(&s[0..6]) {
-            return (err, &s[6..]);
+#[cfg(not(unix))]
+async fn connect_unix(_config: &Config, _path: &str) -> io::Result<Client> {
+    Err(io::Error::other(
+        "Unix domain socket transport is not supported on this platform",
+    ))
+}
+
+async fn finish_connect<T>(stream: T, config: &Config) -> io::Result<Client>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let transport = handshake::perform(
+        stream,
+        config.username.clone(),
+        config.password.clone(),
+        config.database.clone(),
+        config.language.clone(),
+        config.max_block_len,
+    ).await?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    connection::spawn(transport, rx);
+    Ok(Client { requests: tx })
+}
+
+/// Build the `Config` to retry the handshake against after a `^` redirect.
+/// A `mapi://[user@]host:port[/database]` target moves the connection to a
+/// new endpoint, keeping the current database if none is named; a
+/// `merovingian://` target means the same proxy should be retried as-is.
+fn follow_redirect(config: &Config, target: &str) -> io::Result<Config> {
+    let mut next = config.clone();
+    if target.starts_with("mapi://") {
+        let (host, port, database) = parse_mapi_target(target)?;
+        next.hostname = host;
+        next.port = port;
+        if let Some(database) = database {
+            next.database = database;
         }
     }
-    (OperationalError, s)
+    Ok(next)
+}
+
+fn parse_mapi_target(target: &str) -> io::Result<(String, usize, Option<String>)> {
+    let rest = &target["mapi://".len()..];
+    let rest = match rest.find('@') {
+        Some(idx) => &rest[idx + 1..],
+        None => rest,
+    };
+
+    let mut path_parts = rest.splitn(2, '/');
+    let hostport = path_parts.next().ok_or_else(|| invalid_target(target))?;
+    let database = path_parts.next().map(|s| s.to_owned());
+
+    let mut hostport_parts = hostport.rsplitn(2, ':');
+    let port = hostport_parts.next().ok_or_else(|| invalid_target(target))?;
+    let host = hostport_parts.next().ok_or_else(|| invalid_target(target))?;
+    let port: usize = port.parse().map_err(|_| invalid_target(target))?;
+
+    Ok((host.to_owned(), port, database))
 }
 
+fn invalid_target(target: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed redirect target: {:?}", target))
+}
+
+fn connection_gone() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "MAPI connection task is no longer running")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redirect_target_without_a_database_keeps_the_current_one() {
+        let (host, port, database) = parse_mapi_target("mapi://otherhost:50001").unwrap();
+        assert_eq!(host, "otherhost");
+        assert_eq!(port, 50001);
+        assert_eq!(database, None);
+    }
+
+    #[test]
+    fn redirect_target_with_a_database_overrides_the_current_one() {
+        let (host, port, database) = parse_mapi_target("mapi://otherhost:50001/otherdb").unwrap();
+        assert_eq!(host, "otherhost");
+        assert_eq!(port, 50001);
+        assert_eq!(database, Some("otherdb".to_owned()));
+    }
+
+    #[test]
+    fn follow_redirect_falls_back_to_the_current_database() {
+        let config = Config { database: "mydb".to_owned(), ..Config::default() };
+        let next = follow_redirect(&config, "mapi://otherhost:50001").unwrap();
+        assert_eq!(next.hostname, "otherhost");
+        assert_eq!(next.port, 50001);
+        assert_eq!(next.database, "mydb");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod unix_socket_test {
+    use super::*;
+    use byteorder::{WriteBytesExt, LittleEndian};
+    use tokio::net::UnixListener;
+
+    fn encode_test_block(bytes: &[u8]) -> Vec<u8> {
+        let flag: u16 = ((bytes.len() as u16) << 1) + 1;
+        let mut out = Vec::new();
+        out.write_u16::<LittleEndian>(flag).unwrap();
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    #[tokio::test]
+    async fn connect_unix_sends_a_bare_zero_byte_before_the_challenge() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("monetdb-tokio-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut listener = UnixListener::bind(&path).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut preamble = [0u8; 1];
+            stream.read_exact(&mut preamble).await.unwrap();
+            assert_eq!(&preamble, b"0");
+
+            let challenge = b"abcdefgh:merovingian:9:SHA512,SHA256,SHA1,MD5:BIG:SHA512:";
+            stream.write_all(&encode_test_block(challenge)).await.unwrap();
+
+            let mut login = [0u8; 256];
+            let n = stream.read(&mut login).await.unwrap();
+            assert!(n > 0);
+
+            stream.write_all(&encode_test_block(b"")).await.unwrap();
+        });
+
+        let config = Config { socket: Some(path.to_str().unwrap().to_owned()), ..Config::default() };
+        connect_unix(&config, path.to_str().unwrap()).await.unwrap();
+
+        server.await.unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
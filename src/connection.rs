@@ -0,0 +1,176 @@
+//! Drives a single MAPI connection.
+//!
+//! A `Connection` owns the framed transport and serves one query at a
+//! time off an mpsc channel - MAPI is a synchronous request/response
+//! protocol, so there is never more than one query in flight on a socket.
+//! A query that opens a result set replies with a row stream right away
+//! and keeps feeding it as the connection decodes each chunk off the
+//! wire, instead of buffering the whole result before replying.
+
+use std::io;
+
+use futures::{SinkExt, StreamExt};
+use tokio_util::codec::Framed;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::protocol::codec::Frame;
+use crate::protocol::{MapiCodec, MapiMessage};
+
+/// How many decoded body messages the connection task may get ahead of a
+/// slow `query_stream` consumer before `drain_body`'s `tx.send` blocks.
+/// This is what makes the row stream backpressured instead of letting the
+/// connection race ahead of a caller that never polls its receiver.
+const ROW_CHANNEL_CAPACITY: usize = 16;
+
+/// What a query resolves to: a standalone message, or - when it opened a
+/// result set - the message plus a channel streaming its rows/metadata.
+pub enum Response {
+    Message(MapiMessage),
+    Stream(MapiMessage, mpsc::Receiver<io::Result<MapiMessage>>),
+}
+
+pub struct Request {
+    pub sql: String,
+    pub reply: oneshot::Sender<io::Result<Response>>,
+}
+
+/// Spawn the connection's task loop. Returns immediately; the task runs
+/// until `requests` is dropped or the transport errors out.
+pub fn spawn<T>(transport: Framed<T, MapiCodec>, requests: mpsc::UnboundedReceiver<Request>)
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(run(transport, requests));
+}
+
+async fn run<T>(mut transport: Framed<T, MapiCodec>, mut requests: mpsc::UnboundedReceiver<Request>)
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(request) = requests.recv().await {
+        if handle(&mut transport, request).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle<T>(transport: &mut Framed<T, MapiCodec>, request: Request) -> Result<(), ()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Err(err) = transport.send(request.sql).await {
+        let _ = request.reply.send(Err(err));
+        return Err(());
+    }
+
+    let first = match transport.next().await {
+        Some(Ok(frame)) => frame,
+        Some(Err(err)) => {
+            let _ = request.reply.send(Err(err));
+            return Err(());
+        }
+        None => {
+            let _ = request.reply.send(Err(connection_closed()));
+            return Err(());
+        }
+    };
+
+    match first {
+        Frame::Message { message, body: false, block_done } => {
+            let message = match drain_to_block_done(transport, message, block_done).await {
+                Ok(message) => message,
+                Err(err) => {
+                    let _ = request.reply.send(Err(err));
+                    return Err(());
+                }
+            };
+            let _ = request.reply.send(Ok(Response::Message(message)));
+            Ok(())
+        }
+        Frame::Message { message, body: true, .. } => {
+            let (tx, rx) = mpsc::channel(ROW_CHANNEL_CAPACITY);
+            // Reply as soon as the header is in hand; the caller can start
+            // draining `rx` while we keep decoding the rest of the body
+            // below, rather than waiting for the whole result set. If the
+            // caller is already gone, the body is still drained so the next
+            // reply stays correctly framed.
+            let _ = request.reply.send(Ok(Response::Stream(message, rx)));
+            drain_body(transport, tx).await
+        }
+        Frame::Body { .. } => {
+            let _ = request.reply.send(Err(unexpected("a Body frame with no open Message")));
+            Err(())
+        }
+    }
+}
+
+/// A block with no result set can still carry more than one line (an info
+/// line ahead of the real status, say). Keep reading until `block_done`
+/// says the codec has seen the whole block, returning its last message -
+/// the actual completion status, once any leading info lines are past -
+/// so nothing is left buffered to leak into the next request's reply.
+async fn drain_to_block_done<T>(
+    transport: &mut Framed<T, MapiCodec>,
+    mut message: MapiMessage,
+    mut block_done: bool,
+) -> io::Result<MapiMessage>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    while !block_done {
+        match transport.next().await {
+            Some(Ok(Frame::Message { message: next_message, body: false, block_done: next_done })) => {
+                message = next_message;
+                block_done = next_done;
+            }
+            Some(Ok(_)) => return Err(unexpected("a body-opening frame mid non-result block")),
+            Some(Err(err)) => return Err(err),
+            None => return Err(connection_closed()),
+        }
+    }
+    Ok(message)
+}
+
+async fn drain_body<T>(
+    transport: &mut Framed<T, MapiCodec>,
+    mut tx: mpsc::Sender<io::Result<MapiMessage>>,
+) -> Result<(), ()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        match transport.next().await {
+            // Backpressured: this blocks until the caller has room, so the
+            // connection can never decode further ahead than the caller is
+            // willing to consume. If the caller has dropped the receiver,
+            // `send` fails immediately instead of blocking - we keep
+            // draining anyway, since the socket is mid-block and abandoning
+            // it here would leave the next reply misframed.
+            Some(Ok(Frame::Body { chunk: Some(chunk) })) => {
+                let _ = tx.send(Ok(chunk)).await;
+            }
+            Some(Ok(Frame::Body { chunk: None })) => return Ok(()),
+            Some(Ok(Frame::Message { .. })) => {
+                let _ = tx.send(Err(unexpected("a Message frame while draining a body"))).await;
+                return Err(());
+            }
+            Some(Err(err)) => {
+                let _ = tx.send(Err(err)).await;
+                return Err(());
+            }
+            None => {
+                let _ = tx.send(Err(connection_closed())).await;
+                return Err(());
+            }
+        }
+    }
+}
+
+fn connection_closed() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "MAPI connection closed")
+}
+
+fn unexpected(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("unexpected {}", what))
+}
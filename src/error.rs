@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MonetError {
     DatabaseError,
     IntegrityError,
@@ -19,4 +20,18 @@ impl MonetError {
 
         }
     }
+
+    /// Parse a MAPI `!`-prefixed error line (sigil already stripped) into its
+    /// error code and the remaining message, e.g. `"42S02!no such table"` ->
+    /// `(OperationalError, "no such table")`.
+    pub fn parse(s: &str) -> (MonetError, &str) {
+        use self::MonetError::*;
+
+        if s.len() > 6 {
+            if let Some(err) = MonetError::from_mapi_code(&s[0..6]) {
+                return (err, &s[6..]);
+            }
+        }
+        (OperationalError, s)
+    }
 }
@@ -8,14 +8,52 @@
 //!
 //! Protocol uses this codec for encoding/decoding block of message.
 
+use std::collections::VecDeque;
 use std::io;
 use std::io::Cursor;
 use std::str;
 use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
 use bytes::BytesMut;
-use tokio_io::codec::{Encoder, Decoder};
+use tokio_util::codec::{Encoder, Decoder};
+
+use super::message::MapiMessage;
+
+/// One decoded unit of a MAPI response: either one message of a block
+/// (`body` says whether it opens a result set whose rows follow as
+/// `Frame::Body` chunks; `block_done` says whether the caller has seen
+/// every message belonging to this block, or must call `decode` again to
+/// drain the rest before the block is fully consumed), or one of a result
+/// set's follow-up chunks, terminated by `chunk: None`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Message { message: MapiMessage, body: bool, block_done: bool },
+    Body { chunk: Option<MapiMessage> },
+}
+
+pub(super) const MAX_PACKAGE_LENGTH: u16 = (1024 * 8) - 2;
+
+/// Default ceiling on the bytes `MapiCodec` will hold for a single
+/// not-yet-terminated line before giving up, in the absence of a
+/// `with_max_block_len` call.
+pub(crate) const DEFAULT_MAX_BLOCK_LEN: usize = 64 * 1024 * 1024;
+
+/// Split `bytes` into `MAX_PACKAGE_LENGTH`-sized chunks and write each one
+/// out with its little-endian length/last-chunk flag word. Shared with the
+/// handshake's raw block framing, which uses the same wire format before
+/// `MapiCodec` takes over.
+pub(super) fn encode_block(bytes: &[u8], buf: &mut BytesMut) {
+    for chunk in bytes.chunks(MAX_PACKAGE_LENGTH as usize) {
+        let length = chunk.len() as u16;
+        let last = if length < MAX_PACKAGE_LENGTH { 1 } else { 0 };
+        let flag: u16 = (length << 1) + last;
 
-const MAX_PACKAGE_LENGTH: u16 = (1024 * 8) - 2;
+        let mut flag_bytes = vec![];
+        flag_bytes.write_u16::<LittleEndian>(flag).unwrap();
+
+        buf.extend(&flag_bytes);
+        buf.extend(chunk);
+    }
+}
 
 #[derive(Debug, Clone)]
 struct Flag {
@@ -23,80 +61,178 @@ struct Flag {
     last: u16,
 }
 
+/// Does this message open a result set whose rows/metadata follow as a
+/// streaming body, rather than standing alone?
+fn starts_result_set(message: &MapiMessage) -> bool {
+    matches!(*message, MapiMessage::QueryTable { .. })
+}
+
 #[derive(Debug, Clone)]
 pub struct MapiCodec {
+    /// Framing state for the chunk currently being read off the wire.
     flag: Option<Flag>,
-    block: Vec<u8>,
+    /// Bytes read so far for the line that hasn't seen its `\n` yet.
+    line: Vec<u8>,
+    /// Lines parsed out of the wire chunks but not yet handed out as a
+    /// `Frame`.
+    pending: VecDeque<MapiMessage>,
+    /// We've emitted the opening `Frame::Message` of a result set and are
+    /// now working through its body.
+    in_body: bool,
+    /// The block's closing chunk (`last == 1`) has been read and folded
+    /// into `line`/`pending`; once `pending` drains, the body is done.
+    finished: bool,
+    /// Whether any line of the block currently being read has been parsed
+    /// yet - distinguishes a genuinely empty block (bare "ready" prompt)
+    /// from the empty closing wire chunk of a block that already produced
+    /// lines.
+    saw_any_line: bool,
+    max_block_len: usize,
 }
 
 impl MapiCodec {
     pub fn new() -> Self {
         MapiCodec {
             flag: None,
-            block: Vec::new(),
+            line: Vec::new(),
+            pending: VecDeque::new(),
+            in_body: false,
+            finished: false,
+            saw_any_line: false,
+            max_block_len: DEFAULT_MAX_BLOCK_LEN,
         }
     }
-}
 
-impl Decoder for MapiCodec {
-    type Item = String;
-    type Error = io::Error;
+    /// Cap the number of bytes `MapiCodec` will hold for a single
+    /// not-yet-terminated line before `decode` gives up and returns an
+    /// error, instead of buffering without bound.
+    pub fn with_max_block_len(mut self, max_block_len: usize) -> Self {
+        self.max_block_len = max_block_len;
+        self
+    }
 
-    /// A mapi "block" is one entire message, sent in chunks that are
-    /// a maximum of MAX_PACKAGE_LENGTH.
-    ///
-    /// Overall strategy for dealing with async:
-    /// - read flag eagerly, to know what's coming up.
-    /// - only read the following chunk when it's completely available.
-    ///
-    /// This way, we never have to track how much of a block is read.
-    ///
-    /// Also, we only ever try to read one chunk at a time. If we tried to
-    /// do multiple chunks at a time, there could be a lot of partial
-    /// chunks being read. And, better to let tokio tell us when bytes are
-    /// ready, instead of doing it both in tokio and in our logic.
-    fn decode(&mut self, buf:&mut BytesMut) -> io::Result<Option<String>> {
-        // If no flag, it's the start of a chunk.
-        // Therefore, start by reading flag.
-        //
-        // If there's a flag, use current flag (and we know that the following
-        // chunk has not been pulled out of the buffer yet).
+    /// Read one length-prefixed wire chunk into `self.line`, splitting off
+    /// and parsing every complete `\n`-terminated line it completes into
+    /// `self.pending` as it goes. This is what makes a huge result set
+    /// stream out a row at a time instead of only being materialized once
+    /// the entire block (which can be gigabytes) has arrived.
+    fn read_chunk(&mut self, buf: &mut BytesMut) -> io::Result<bool> {
         if self.flag.is_none() {
-            if buf.len() < 2 { return Ok(None) };
+            if buf.len() < 2 { return Ok(false) };
             let flag = buf.split_to(2);
             let flag = Cursor::new(flag).read_u16::<LittleEndian>().unwrap();
-            self.flag = Some(Flag {
-                length: flag >> 1,
-                last: flag & 1,
-            });
+            self.flag = Some(Flag { length: flag >> 1, last: flag & 1 });
         }
 
-        // Now that there is a flag, check that there is a full chunk
-        // available to read. If not, wait for more bytes.
         let length = self.flag.as_ref().unwrap().length as usize;
-        if length > buf.len() { return Ok(None) };
+        if length > buf.len() { return Ok(false) };
 
-        // We know there is a full chunk available to read, now we can
-        // read it!
         let bytes = buf.split_to(length);
-
-        // Chunk is read; now append to block. Done with one chunk!
-        self.block.extend_from_slice(&bytes[..]);
-
-        // Check if all chunks are appended into block. If so, parse the whole
-        // block. If block is not yet completed, go back for more chunks.
-        // (And flag needs to be reset whether it's chunk or block completed.
-        // Incompletes should have short-circuited above)
         let last = self.flag.as_ref().unwrap().last;
         self.flag = None;
 
-        if last == 1 {
-            match str::from_utf8(&self.block) {
-                Ok(s) => Ok(Some(s.to_owned())),
-                Err(_) => Err(io::Error::new(io::ErrorKind::Other, "invalid UTF-8")),
+        self.line.extend_from_slice(&bytes[..]);
+        if self.line.len() > self.max_block_len {
+            self.line.clear();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line exceeded max_block_len of {} bytes", self.max_block_len),
+            ));
+        }
+
+        self.drain_lines(last == 1)?;
+        Ok(true)
+    }
+
+    /// Move every complete line out of `self.line` and into `self.pending`.
+    /// When `block_done` is set (the wire chunk just read was the block's
+    /// last one), also flush a trailing, not-newline-terminated remainder
+    /// as the final line, and mark the block finished.
+    fn drain_lines(&mut self, block_done: bool) -> io::Result<()> {
+        while let Some(pos) = self.line.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.line.drain(..=pos).collect();
+            let line = str::from_utf8(&line[..line.len() - 1])
+                .map_err(|_| invalid_utf8())?;
+            self.pending.push_back(MapiMessage::parse_line(line)?);
+            self.saw_any_line = true;
+        }
+
+        if block_done {
+            if !self.line.is_empty() {
+                let line = str::from_utf8(&self.line).map_err(|_| invalid_utf8())?.to_owned();
+                self.line.clear();
+                self.pending.push_back(MapiMessage::parse_line(&line)?);
+                self.saw_any_line = true;
+            } else if !self.saw_any_line {
+                // A block with no lines at all: the bare "ready" prompt.
+                self.pending.push_back(MapiMessage::Prompt);
+            }
+            self.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::other("invalid UTF-8")
+}
+
+impl Decoder for MapiCodec {
+    type Item = Frame;
+    type Error = io::Error;
+
+    /// A mapi "block" is one entire reply, sent as chunks that are at most
+    /// `MAX_PACKAGE_LENGTH` bytes each.
+    ///
+    /// Overall strategy for dealing with async:
+    /// - read flag eagerly, to know what's coming up.
+    /// - only read the following chunk when it's completely available.
+    ///
+    /// This way, we never have to track how much of a block is read.
+    ///
+    /// A completed block can contain several MAPI lines - a query header
+    /// followed by its metadata rows and tuples, say. Rather than buffer
+    /// the whole block before parsing a single line of it, each wire chunk
+    /// is split into lines as soon as it arrives (see `read_chunk`): a
+    /// `QueryTable` header is emitted as a `Frame::Message` with `body:
+    /// true`, and the rest of the lines follow as `Frame::Body` chunks -
+    /// decoded from the wire a chunk at a time - terminated by
+    /// `Frame::Body { chunk: None }`. This lets a huge result set stream
+    /// out row by row without ever holding the whole block in memory.
+    /// A block with no result set can still hold more than one line;
+    /// `Frame::Message { block_done: false, .. }` tells the caller to call
+    /// `decode` again rather than treat the block as answered yet.
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        loop {
+            if self.in_body {
+                if let Some(message) = self.pending.pop_front() {
+                    return Ok(Some(Frame::Body { chunk: Some(message) }));
+                }
+                if self.finished {
+                    self.in_body = false;
+                    self.finished = false;
+                    self.saw_any_line = false;
+                    return Ok(Some(Frame::Body { chunk: None }));
+                }
+            } else if let Some(message) = self.pending.pop_front() {
+                self.in_body = starts_result_set(&message);
+                // A block can hold more than one non-result line (an info
+                // line ahead of the real status, say); block_done tells the
+                // caller whether this was the last of them, or whether more
+                // Frame::Message calls are needed to drain the rest of the
+                // block before it's safe to treat the reply as complete.
+                let block_done = !self.in_body && self.pending.is_empty() && self.finished;
+                if block_done {
+                    self.finished = false;
+                    self.saw_any_line = false;
+                }
+                return Ok(Some(Frame::Message { message, body: self.in_body, block_done }));
+            }
+
+            if !self.read_chunk(buf)? {
+                return Ok(None);
             }
-        } else {
-            Ok(None)
         }
     }
 }
@@ -105,24 +241,11 @@ impl Encoder for MapiCodec {
     type Item = String;
     type Error = io::Error;
 
-    fn encode(&mut self, msg: String, buf: &mut BytesMut) -> io::Result<()> {
+    fn encode(&mut self, item: Self::Item, buf: &mut BytesMut) -> io::Result<()> {
         // Split at MAX_PACKAGE_LENGTH, then insert flag, then join,
         // then append. Basically, preprocess and then send whole
         // block at once. tokio will take care of streaming it out.
-        let bytes = msg.as_bytes();
-        let chunks = bytes.chunks(MAX_PACKAGE_LENGTH as usize);
-
-        for chunk in chunks {
-            let length = chunk.len() as u16;
-            let last = if length < MAX_PACKAGE_LENGTH { 1 } else { 0 };
-            let flag: u16 = (length << 1) + last;
-
-            let mut flag_bytes = vec![];
-            flag_bytes.write_u16::<LittleEndian>(flag).unwrap();
-
-            buf.extend(&flag_bytes);
-            buf.extend(chunk);
-        }
+        encode_block(item.as_bytes(), buf);
         Ok(())
     }
 }
@@ -131,68 +254,171 @@ impl Encoder for MapiCodec {
 mod test {
     use super::*;
     use bytes::BytesMut;
-    use std::io::Cursor;
-    use byteorder::{ReadBytesExt, LittleEndian};
+    use crate::error::MonetError;
 
     const MAX_PACKAGE_LENGTH: u16 = (1024 * 8) -2;
 
+    fn assert_error_frame(frame: Option<Frame>, expected: &str) {
+        match frame {
+            Some(Frame::Message { message: MapiMessage::Error(err, msg), body, block_done }) => {
+                assert_eq!(err, MonetError::OperationalError);
+                assert_eq!(msg, expected);
+                assert!(!body);
+                assert!(block_done);
+            }
+            other => panic!("expected an Error message, got {:?}", other),
+        }
+    }
+
     #[test]
     fn codec_decode_simple_decode() {
         // basic test case
-        let test_input = "this is test input";
+        let test_input = "!42S02!no such table";
         let test_input_bytes = test_input.as_bytes();
         let flag = (test_input_bytes.len() << 1) + 1;
         let mut flag_bytes = Vec::new();
-        flag_bytes.write_u16::<LittleEndian>(flag as u16);
+        flag_bytes.write_u16::<LittleEndian>(flag as u16).unwrap();
 
         let mut input = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
         input.extend(flag_bytes);
         input.extend(test_input_bytes);
         let mut codec = MapiCodec::new();
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(Some(test_input.to_owned())));
+        let output = codec.decode(&mut input).unwrap();
+        assert_error_frame(output, "no such table");
 
 
         // flag is set for length too long
-        let test_input = "this is test input";
+        let test_input = "!42S02!no such table";
         let test_input_bytes = test_input.as_bytes();
         let flag = ((test_input_bytes.len() + 1) << 1) + 1;
         let mut flag_bytes = Vec::new();
-        flag_bytes.write_u16::<LittleEndian>(flag as u16);
+        flag_bytes.write_u16::<LittleEndian>(flag as u16).unwrap();
 
         let mut input = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
         input.extend(flag_bytes);
         input.extend(test_input_bytes);
         let mut codec = MapiCodec::new();
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(None));
+        let output = codec.decode(&mut input).unwrap();
+        assert!(output.is_none());
 
         // Two chunks;
-        let test_input = "this is test input";
+        let test_input = "!42S02!no such table";
         let test_input_bytes = test_input.as_bytes();
         let flag = (test_input_bytes.len() << 1) + 1;
         let mut flag_bytes = Vec::new();
-        flag_bytes.write_u16::<LittleEndian>(flag as u16);
+        flag_bytes.write_u16::<LittleEndian>(flag as u16).unwrap();
 
         let mut input = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
         input.extend(flag_bytes);
         input.extend(&test_input_bytes[..5]);
         let mut codec = MapiCodec::new();
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(None));
+        let output = codec.decode(&mut input).unwrap();
+        assert!(output.is_none());
         input.extend(&test_input_bytes[5..]);
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(Some(test_input.to_owned())));
+        let output = codec.decode(&mut input).unwrap();
+        assert_error_frame(output, "no such table");
+    }
+
+    #[test]
+    fn codec_decode_rejects_oversized_block() {
+        // Two non-final chunks whose combined length exceeds a tiny
+        // max_block_len, so the running total - not the declared chunk
+        // length - is what trips the error.
+        let mut codec = MapiCodec::new().with_max_block_len(10);
+
+        let first = vec![b'a'; 8];
+        let flag = (first.len() << 1) as u16; // last bit unset: more to come
+        let mut flag_bytes = Vec::new();
+        flag_bytes.write_u16::<LittleEndian>(flag).unwrap();
+
+        let mut input = BytesMut::with_capacity(32);
+        input.extend(flag_bytes);
+        input.extend(&first);
+        assert!(codec.decode(&mut input).unwrap().is_none());
+
+        let second = vec![b'b'; 8];
+        let flag = (second.len() << 1) as u16;
+        let mut flag_bytes = Vec::new();
+        flag_bytes.write_u16::<LittleEndian>(flag).unwrap();
+        input.extend(flag_bytes);
+        input.extend(&second);
+
+        let err = codec.decode(&mut input).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn codec_decode_streams_body_before_final_wire_chunk_arrives() {
+        // A QueryTable header followed by one metadata row, sent as two
+        // separate (non-final) wire chunks of the same block, plus a third,
+        // final, empty chunk that closes the block. The header and the
+        // metadata row must each come out as soon as their own chunk
+        // arrives - not only once the whole block (including the closing
+        // chunk) is available.
+        let mut codec = MapiCodec::new();
+        let mut input = BytesMut::new();
+
+        push_chunk(&mut input, b"&1 18 0 1 0\n", false);
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Message { message: MapiMessage::QueryTable { .. }, body: true, .. }) => {}
+            other => panic!("expected a QueryTable message, got {:?}", other),
+        }
+        assert!(codec.decode(&mut input).unwrap().is_none());
+
+        push_chunk(&mut input, b"% a # name\n", false);
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Body { chunk: Some(MapiMessage::Header { name, .. }) }) => {
+                assert_eq!(name, "name");
+            }
+            other => panic!("expected a Header body chunk, got {:?}", other),
+        }
+        assert!(codec.decode(&mut input).unwrap().is_none());
+
+        push_chunk(&mut input, b"", true);
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Body { chunk: None }) => {}
+            other => panic!("expected the body to close, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn codec_decode_reports_block_done_only_after_the_last_non_body_line() {
+        // A block with no result set can still carry more than one line -
+        // an info line ahead of the real status, say. block_done must stay
+        // false until the last one, so a caller that only wants the final
+        // reply knows it has to keep draining instead of treating the
+        // first line as the whole answer.
+        let mut codec = MapiCodec::new();
+        let mut input = BytesMut::new();
+
+        push_chunk(&mut input, b"# a server comment\n&4\n", true);
+
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Message { message: MapiMessage::Info(_), body: false, block_done: false }) => {}
+            other => panic!("expected a non-final Info message, got {:?}", other),
+        }
+
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Message { message: MapiMessage::Transaction, body: false, block_done: true }) => {}
+            other => panic!("expected the final Transaction message, got {:?}", other),
+        }
+    }
+
+    fn push_chunk(buf: &mut BytesMut, bytes: &[u8], last: bool) {
+        let flag: u16 = ((bytes.len() as u16) << 1) + if last { 1 } else { 0 };
+        let mut flag_bytes = Vec::new();
+        flag_bytes.write_u16::<LittleEndian>(flag).unwrap();
+        buf.extend(flag_bytes);
+        buf.extend(bytes);
     }
 
     #[test]
     fn codec_test_simple_encode() {
         let mut buf = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
-        let test_output = "this is test output";
+        let test_output = "this is test output".to_owned();
         let mut codec = MapiCodec::new();
-        codec.encode(test_output.to_owned(), &mut buf);
+        codec.encode(test_output, &mut buf).unwrap();
 
-        println!("{:?}", buf);
         assert_eq!(
             &buf[..],
             &[39, 0, 116, 104, 105, 115, 32, 105,
@@ -204,11 +430,11 @@ mod test {
     #[test]
     fn codec_test_simple_encode_decode() {
         let mut buf = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
-        let test_input = "this is test output";
+        let test_input = "!42S02!boom".to_owned();
         let mut codec = MapiCodec::new();
-        codec.encode(test_input.to_owned(), &mut buf);
-        let output = codec.decode(&mut buf);
-        assert_eq!(output.unwrap().unwrap(), test_input);
+        codec.encode(test_input, &mut buf).unwrap();
+        let output = codec.decode(&mut buf).unwrap();
+        assert_error_frame(output, "boom");
     }
 
     // This is not a strong test, since it just tests a typical input.
@@ -218,11 +444,37 @@ mod test {
         let mut input = BytesMut::with_capacity(MAX_PACKAGE_LENGTH as usize + 2);
         input.extend(REAL_FLAG_BYTES);
         let mut codec = MapiCodec::new();
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(None));
+        let output = codec.decode(&mut input).unwrap();
+        assert!(output.is_none());
         input.extend(REAL_INPUT_BYTES);
-        let output = codec.decode(&mut input);
-        assert_eq!(output.map_err(|_|()), Ok(Some(REAL_INPUT.to_owned())));
+
+        // A single completed block unpacks into the query header, followed
+        // by one `Header` body chunk per metadata row and a final `None`
+        // chunk closing the body - draining one `decode` call at a time.
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Message { message: MapiMessage::QueryTable { id, rows, cols, query_id }, body, .. }) => {
+                assert_eq!((id, rows, cols, query_id), (18, 0, 22, 0));
+                assert!(body);
+            }
+            other => panic!("expected a QueryTable message, got {:?}", other),
+        }
+
+        let names = vec!["table_name", "name", "type", "length"];
+        for expected_name in names {
+            match codec.decode(&mut input).unwrap() {
+                Some(Frame::Body { chunk: Some(MapiMessage::Header { name, values }) }) => {
+                    assert_eq!(name, expected_name);
+                    assert_eq!(values.len(), 22);
+                }
+                other => panic!("expected a Header body chunk, got {:?}", other),
+            }
+        }
+
+        match codec.decode(&mut input).unwrap() {
+            Some(Frame::Body { chunk: None }) => {}
+            other => panic!("expected the body to close, got {:?}", other),
+        }
+        assert!(codec.decode(&mut BytesMut::new()).unwrap().is_none());
     }
 
     // from tcpdump listening to message sent from monetdb to client,
@@ -269,6 +521,4 @@ mod test {
           44, 9, 48, 44, 9, 48, 44, 9, 49, 44, 9, 49, 44, 9, 49, 44, 9, 49, 44, 9, 48,
           44, 9, 48, 44, 9, 48, 44, 9, 48, 44, 9, 49, 32, 35, 32, 108, 101, 110, 103,
           116, 104, 10];
-
-    const REAL_INPUT: &'static str = "&1 18 0 22 0\n% .L50,\t.L52,\t.L54,\t.L56,\t.L61,\t.L63,\t.L65,\t.L67,\t.L71,\t.L73,\t.L76,\t.L101,\t.L103,\t.L105,\t.L107,\t.L111,\t.L113,\t.L117,\t.L122,\t.L125,\t.L130,\t.L133 # table_name\n% TABLE_CAT,\tTABLE_SCHEM,\tTABLE_NAME,\tCOLUMN_NAME,\tDATA_TYPE,\tTYPE_NAME,\tCOLUMN_SIZE,\tBUFFER_LENGTH,\tDECIMAL_DIGITS,\tNUM_PREC_RADIX,\tNULLABLE,\tREMARKS,\tCOLUMN_DEF,\tSQL_DATA_TYPE,\tSQL_DATETIME_SUB,\tCHAR_OCTET_LENGTH,\tORDINAL_POSITION,\tIS_NULLABLE,\tSCOPE_CATALOG,\tSCOPE_SCHEMA,\tSCOPE_TABLE,\tSOURCE_DATA_TYPE # name\n% char,\tvarchar,\tvarchar,\tvarchar,\tsmallint,\tvarchar,\tint,\ttinyint,\tint,\ttinyint,\tint,\tvarchar,\tvarchar,\ttinyint,\ttinyint,\ttinyint,\tbigint,\tvarchar,\tvarchar,\tvarchar,\tvarchar,\tsmallint # type\n% 3,\t0,\t0,\t0,\t1,\t0,\t1,\t1,\t1,\t1,\t1,\t0,\t0,\t1,\t1,\t1,\t1,\t0,\t0,\t0,\t0,\t1 # length\n";
 }
@@ -0,0 +1,198 @@
+//! MAPI v9 challenge/response login handshake.
+//!
+//! Implementation follows
+//! https://github.com/gijzelaerr/pymonetdb/blob/master/pymonetdb/mapi.py
+//! which documents the salt/algorithm exchange every MAPI client goes
+//! through right after the TCP connection is established, before any
+//! query can be sent.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Cursor;
+use std::str;
+
+use byteorder::{ReadBytesExt, LittleEndian};
+use bytes::BytesMut;
+use crypto_hash::{Algorithm, hex_digest};
+use futures::{SinkExt, StreamExt};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::error::MonetError;
+use super::codec::{MapiCodec, encode_block};
+
+/// Preference order for the challenge-hash algorithm, strongest first.
+const HASH_PREFERENCE: &[&str] = &["SHA512", "SHA256", "SHA1", "MD5"];
+
+/// Run the login handshake over a freshly connected transport and, on
+/// success, hand back the same transport framed for ordinary MAPI traffic.
+pub async fn perform<T>(
+    io: T,
+    username: String,
+    password: String,
+    database: String,
+    language: String,
+    max_block_len: usize,
+) -> io::Result<Framed<T, MapiCodec>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut framed = Framed::new(io, RawBlockCodec::new());
+
+    let challenge = framed.next().await.ok_or_else(eof)??;
+    let response = build_response(&challenge, &username, &password, &database, &language)?;
+    framed.send(response).await?;
+
+    let reply = framed.next().await.ok_or_else(eof)??;
+    match reply.chars().next() {
+        None => {
+            let codec = MapiCodec::new().with_max_block_len(max_block_len);
+            Ok(Framed::new(framed.into_inner(), codec))
+        }
+        Some('!') => {
+            let (err, msg) = MonetError::parse(&reply[1..]);
+            Err(io::Error::other(format!("{:?}: {}", err, msg)))
+        }
+        Some('^') => Err(io::Error::other(RedirectError(reply[1..].to_owned()))),
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected handshake reply: {:?}", reply),
+        )),
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "server closed connection during handshake")
+}
+
+/// The handshake replied with a `^` redirect line instead of logging in.
+/// Carried inside an `io::Error` so `Client::connect` can tell a redirect
+/// apart from a genuine handshake failure and retry against the new target.
+#[derive(Debug)]
+pub struct RedirectError(pub String);
+
+impl fmt::Display for RedirectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "server requested redirect to {}", self.0)
+    }
+}
+
+impl error::Error for RedirectError {
+    fn description(&self) -> &str {
+        "MAPI redirect"
+    }
+}
+
+/// If `err` wraps a handshake redirect, return the `mapi://`/`merovingian://`
+/// target it named.
+pub fn redirect_target(err: &io::Error) -> Option<&str> {
+    err.get_ref()
+        .and_then(|e| e.downcast_ref::<RedirectError>())
+        .map(|e| e.0.as_str())
+}
+
+/// Build the `{endian}:{username}:{{HASHNAME}}{h}:{language}:{database}:`
+/// login line for a `salt:identity:protocol:hashalgos:endian:pwhashalgo:`
+/// challenge.
+fn build_response(
+    challenge: &str,
+    username: &str,
+    password: &str,
+    database: &str,
+    language: &str,
+) -> io::Result<String> {
+    let mut fields = challenge.split(':');
+    let salt = fields.next().ok_or_else(|| malformed(challenge))?;
+    let _identity = fields.next().ok_or_else(|| malformed(challenge))?;
+    let _protocol = fields.next().ok_or_else(|| malformed(challenge))?;
+    let hashalgos = fields.next().ok_or_else(|| malformed(challenge))?;
+    let _endian = fields.next().ok_or_else(|| malformed(challenge))?;
+    let pwhashalgo = fields.next().ok_or_else(|| malformed(challenge))?;
+
+    let pw_algo = algorithm_named(pwhashalgo).ok_or_else(|| unsupported(pwhashalgo))?;
+    let pw = hex_digest(pw_algo, password.as_bytes());
+
+    let (hash_name, h_algo) = HASH_PREFERENCE
+        .iter()
+        .find(|name| hashalgos.split(',').any(|a| a == **name))
+        .and_then(|name| algorithm_named(name).map(|algo| (*name, algo)))
+        .ok_or_else(|| unsupported(hashalgos))?;
+    let h = hex_digest(h_algo, format!("{}{}", pw, salt).as_bytes());
+
+    Ok(format!("LIT:{}:{{{}}}{}:{}:{}:", username, hash_name, h, language, database))
+}
+
+fn algorithm_named(name: &str) -> Option<Algorithm> {
+    match name {
+        "SHA512" => Some(Algorithm::SHA512),
+        "SHA256" => Some(Algorithm::SHA256),
+        "SHA1" => Some(Algorithm::SHA1),
+        "MD5" => Some(Algorithm::MD5),
+        _ => None,
+    }
+}
+
+fn malformed(challenge: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed handshake challenge: {:?}", challenge))
+}
+
+fn unsupported(names: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("no supported hash algorithm in {:?}", names))
+}
+
+/// The bare block framing (length-prefixed chunks, no sigil parsing) used
+/// only for the handshake, before any `MapiMessage` can be decoded.
+struct RawBlockCodec {
+    flag: Option<u16>,
+    block: Vec<u8>,
+}
+
+impl RawBlockCodec {
+    fn new() -> Self {
+        RawBlockCodec { flag: None, block: Vec::new() }
+    }
+}
+
+impl Decoder for RawBlockCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.flag.is_none() {
+            if buf.len() < 2 { return Ok(None) };
+            let flag = buf.split_to(2);
+            self.flag = Some(Cursor::new(flag).read_u16::<LittleEndian>().unwrap());
+        }
+
+        let flag = *self.flag.as_ref().unwrap();
+        let length = (flag >> 1) as usize;
+        if length > buf.len() { return Ok(None) };
+
+        let bytes = buf.split_to(length);
+        self.block.extend_from_slice(&bytes[..]);
+        let last = flag & 1;
+        self.flag = None;
+
+        if last != 1 {
+            return Ok(None);
+        }
+
+        let block = match str::from_utf8(&self.block) {
+            Ok(s) => s.trim_end_matches('\n').to_owned(),
+            Err(_) => return Err(io::Error::other("invalid UTF-8")),
+        };
+        self.block.clear();
+        Ok(Some(block))
+    }
+}
+
+impl Encoder for RawBlockCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: String, buf: &mut BytesMut) -> io::Result<()> {
+        encode_block(item.as_bytes(), buf);
+        Ok(())
+    }
+}
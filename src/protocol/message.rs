@@ -0,0 +1,214 @@
+//! Typed MAPI protocol messages.
+//!
+//! A decoded MAPI block is one or more `\n`-separated lines, each beginning
+//! with a sigil that identifies the kind of line (`&`, `%`, `[`, `!`, `^`, or
+//! the `=OK`/empty-prompt forms). `MapiMessage` names each of those so
+//! callers can match on structure instead of re-parsing the sigils
+//! themselves.
+
+use std::io;
+
+use crate::error::MonetError;
+
+const MSG_MORE: &str = "\\1\\2";
+
+/// One parsed line (or whole-block reply) from a MAPI server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapiMessage {
+    /// `&1`/`&6`: a result set header - row/column counts for a `SELECT`.
+    QueryTable { id: i64, rows: i64, cols: i64, query_id: i64 },
+    /// `&2`: rows affected by an `INSERT`/`UPDATE`/`DELETE`.
+    QueryUpdate { affected_rows: i64, last_id: i64 },
+    /// `&3`: a schema-changing statement (`CREATE TABLE`, ...) completed.
+    Schema,
+    /// `&4`: a transaction statement (`COMMIT`, `ROLLBACK`, ...) completed.
+    Transaction,
+    /// `&5`: a prepared statement id.
+    Prepare { id: i64 },
+    /// `%`: one row of result-set metadata (names, types, lengths, ...).
+    Header { name: String, values: Vec<String> },
+    /// `[ ... ]`: one data row of a result set.
+    Tuple(Vec<Option<String>>),
+    /// `!`: a server error.
+    Error(MonetError, String),
+    /// `^`: redirect to another MAPI endpoint.
+    Redirect(String),
+    /// `=OK`: an unconditional success reply.
+    Ok,
+    /// An empty block: the server is ready for the next command.
+    Prompt,
+    /// The continuation prompt sent while more input is expected.
+    More,
+    /// `#`: a free-form info/comment line (the historical `MSG_INFO`
+    /// marker), or any other line whose sigil isn't otherwise recognized.
+    /// Carried through rather than treated as fatal, since the server can
+    /// legitimately emit these alongside a query's real reply.
+    Info(String),
+}
+
+/// One data row of a streamed result set, with `None` marking a SQL `NULL`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row(pub Vec<Option<String>>);
+
+impl MapiMessage {
+    /// Parse a full MAPI block into its constituent messages, in order.
+    pub fn parse_block(block: &str) -> io::Result<Vec<MapiMessage>> {
+        if block.is_empty() {
+            return Ok(vec![MapiMessage::Prompt]);
+        }
+        block.lines().map(MapiMessage::parse_line).collect()
+    }
+
+    /// Parse a single line of a decoded MAPI block into its message variant.
+    pub(crate) fn parse_line(line: &str) -> io::Result<MapiMessage> {
+        if line.is_empty() {
+            return Ok(MapiMessage::Prompt);
+        }
+        if line == MSG_MORE {
+            return Ok(MapiMessage::More);
+        }
+        if line == "=OK" {
+            return Ok(MapiMessage::Ok);
+        }
+
+        let (sigil, rest) = line.split_at(1);
+        match sigil {
+            "!" => {
+                let (err, msg) = MonetError::parse(rest);
+                Ok(MapiMessage::Error(err, msg.to_owned()))
+            }
+            "^" => Ok(MapiMessage::Redirect(rest.trim().to_owned())),
+            "%" => parse_header(rest),
+            "[" => parse_tuple(rest),
+            "&" => parse_query(rest),
+            "#" => Ok(MapiMessage::Info(rest.trim().to_owned())),
+            // An unrecognized sigil shouldn't tear down the connection -
+            // carry the line through as info rather than erroring.
+            _ => Ok(MapiMessage::Info(line.to_owned())),
+        }
+    }
+}
+
+fn parse_query(rest: &str) -> io::Result<MapiMessage> {
+    let mut fields = rest.split_whitespace();
+    let kind = fields.next().ok_or_else(|| invalid_line(rest))?;
+    match kind {
+        "1" | "6" => Ok(MapiMessage::QueryTable {
+            id: next_i64(&mut fields, rest)?,
+            rows: next_i64(&mut fields, rest)?,
+            cols: next_i64(&mut fields, rest)?,
+            query_id: fields.next().and_then(|s| s.parse().ok()).unwrap_or(0),
+        }),
+        "2" => Ok(MapiMessage::QueryUpdate {
+            affected_rows: next_i64(&mut fields, rest)?,
+            last_id: fields.next().and_then(|s| s.parse().ok()).unwrap_or(-1),
+        }),
+        "3" => Ok(MapiMessage::Schema),
+        "4" => Ok(MapiMessage::Transaction),
+        "5" => Ok(MapiMessage::Prepare { id: next_i64(&mut fields, rest)? }),
+        _ => Err(invalid_line(rest)),
+    }
+}
+
+fn next_i64<'a, I: Iterator<Item = &'a str>>(fields: &mut I, line: &str) -> io::Result<i64> {
+    fields.next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_line(line))
+}
+
+fn parse_header(rest: &str) -> io::Result<MapiMessage> {
+    let rest = rest.trim_start();
+    let mut parts = rest.rsplitn(2, '#');
+    let name = parts.next().ok_or_else(|| invalid_line(rest))?.trim();
+    let data = parts.next().unwrap_or("").trim();
+    let values = if data.is_empty() {
+        Vec::new()
+    } else {
+        data.split(",\t").map(|v| v.trim().to_owned()).collect()
+    };
+    Ok(MapiMessage::Header { name: name.to_owned(), values })
+}
+
+fn parse_tuple(rest: &str) -> io::Result<MapiMessage> {
+    let inner = rest.trim().trim_start_matches('[').trim_end_matches(']').trim();
+    let values = if inner.is_empty() {
+        Vec::new()
+    } else {
+        inner
+            .split(",\t")
+            .map(|v| {
+                let v = v.trim();
+                if v == "NULL" {
+                    None
+                } else {
+                    Some(v.trim_matches('"').to_owned())
+                }
+            })
+            .collect()
+    };
+    Ok(MapiMessage::Tuple(values))
+}
+
+fn invalid_line(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed MAPI line: {:?}", line))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_error_line() {
+        let msg = MapiMessage::parse_line("!42S02!no such table 'foo'").unwrap();
+        assert_eq!(msg, MapiMessage::Error(MonetError::OperationalError, "no such table 'foo'".to_owned()));
+    }
+
+    #[test]
+    fn parses_query_table_header() {
+        let msg = MapiMessage::parse_line("&1 18 0 22 0").unwrap();
+        assert_eq!(msg, MapiMessage::QueryTable { id: 18, rows: 0, cols: 22, query_id: 0 });
+    }
+
+    #[test]
+    fn parses_metadata_header() {
+        let msg = MapiMessage::parse_line("% a,\tb,\tc # name").unwrap();
+        assert_eq!(msg, MapiMessage::Header {
+            name: "name".to_owned(),
+            values: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+        });
+    }
+
+    #[test]
+    fn parses_tuple_with_null() {
+        let msg = MapiMessage::parse_line("[ \"a\",\tNULL,\t3\t]").unwrap();
+        assert_eq!(msg, MapiMessage::Tuple(vec![
+            Some("a".to_owned()),
+            None,
+            Some("3".to_owned()),
+        ]));
+    }
+
+    #[test]
+    fn parses_redirect() {
+        let msg = MapiMessage::parse_line("^mapi://localhost:50000/mydb").unwrap();
+        assert_eq!(msg, MapiMessage::Redirect("mapi://localhost:50000/mydb".to_owned()));
+    }
+
+    #[test]
+    fn empty_block_is_prompt() {
+        let msgs = MapiMessage::parse_block("").unwrap();
+        assert_eq!(msgs, vec![MapiMessage::Prompt]);
+    }
+
+    #[test]
+    fn parses_info_line() {
+        let msg = MapiMessage::parse_line("# a comment from the server").unwrap();
+        assert_eq!(msg, MapiMessage::Info("a comment from the server".to_owned()));
+    }
+
+    #[test]
+    fn unrecognized_sigil_is_treated_as_info_not_an_error() {
+        let msg = MapiMessage::parse_line("@weird line").unwrap();
+        assert_eq!(msg, MapiMessage::Info("@weird line".to_owned()));
+    }
+}